@@ -9,21 +9,24 @@ from fastapi import FastAPI, HTTPException
 from fastapi.middleware.cors import CORSMiddleware
 from pydantic import BaseModel, Field
 
+from loopback_guard import (
+    LOCAL_ORIGINS,
+    LoopbackGuardMiddleware,
+    extension_origin_policy,
+    parse_csv,
+    require_loopback_bind,
+)
 from search import SearchHit, rank_hits, search_image_cached, search_text_cached
 
 
 DEFAULT_PORT = int(os.getenv("HARBOR_API_PORT", "8765"))
 DEFAULT_HOST = os.getenv("HARBOR_API_HOST", "127.0.0.1")
-DEFAULT_ORIGINS = os.getenv(
-    "HARBOR_API_CORS_ORIGINS",
-    "http://localhost,http://127.0.0.1",
+DEFAULT_ORIGINS = parse_csv(os.getenv("HARBOR_API_CORS_ORIGINS"), LOCAL_ORIGINS)
+EXTENSION_ORIGINS, EXTENSION_ORIGIN_PATTERN = extension_origin_policy(
+    os.getenv("HARBOR_API_EXTENSION_ORIGINS"), "HARBOR_API_EXTENSION_ORIGINS"
 )
 
 
-def _parse_origins(value: str) -> List[str]:
-    return [origin.strip() for origin in value.split(",") if origin.strip()]
-
-
 class SearchRequest(BaseModel):
     query: str = Field(min_length=1, max_length=300)
     delivery: Literal["cheapest", "fastest"] = "cheapest"
@@ -39,15 +42,21 @@ class SearchRequest(BaseModel):
 
 app = FastAPI(title="Harbor Search API", version="0.1.0")
 
-cors_origins = _parse_origins(DEFAULT_ORIGINS)
+allowed_origins = DEFAULT_ORIGINS + EXTENSION_ORIGINS
 app.add_middleware(
     CORSMiddleware,
-    allow_origins=cors_origins if cors_origins else ["*"],
-    allow_origin_regex=r"^(moz-extension|chrome-extension)://.*$",
+    allow_origins=allowed_origins,
+    allow_origin_regex=EXTENSION_ORIGIN_PATTERN,
     allow_credentials=True,
     allow_methods=["*"],
     allow_headers=["*"],
 )
+app.add_middleware(
+    LoopbackGuardMiddleware,
+    allowed_origins=allowed_origins,
+    origin_regex=EXTENSION_ORIGIN_PATTERN,
+    port=DEFAULT_PORT,
+)
 
 
 def _engines_for_privacy(privacy: str) -> List[str]:
@@ -165,4 +174,5 @@ async def search(payload: SearchRequest) -> Dict[str, Any]:
 if __name__ == "__main__":
     import uvicorn
 
+    require_loopback_bind(DEFAULT_HOST, "HARBOR_API_HOST")
     uvicorn.run(app, host=DEFAULT_HOST, port=DEFAULT_PORT, reload=False)
@@ -0,0 +1,145 @@
+"""Host/Origin checks shared by the local HTTP servers (search API and MCP)."""
+from __future__ import annotations
+
+import ipaddress
+import json
+import logging
+import re
+from typing import Any, Callable, Iterable, List, Optional, Tuple
+from urllib.parse import urlsplit
+
+
+logger = logging.getLogger(__name__)
+
+LOCAL_ORIGINS = ("http://localhost", "http://127.0.0.1")
+EXTENSION_ORIGIN_REGEX = r"^(moz-extension|chrome-extension)://.*$"
+
+
+def parse_csv(value: Optional[str], default: Iterable[str]) -> List[str]:
+    """Split a comma-separated setting; an empty value falls back to ``default``."""
+    items = [item.strip() for item in (value or "").split(",") if item.strip()]
+    return items if items else list(default)
+
+
+def is_loopback(host: str) -> bool:
+    if host.lower() == "localhost":
+        return True
+    try:
+        return ipaddress.ip_address(host.strip("[]")).is_loopback
+    except ValueError:
+        return False
+
+
+def extension_origin_policy(value: Optional[str], setting: str) -> Tuple[List[str], Optional[str]]:
+    """Return ``(origins, regex)`` for extension callers.
+
+    Pinned origins are matched exactly. When ``setting`` is unset, any extension
+    origin is accepted and a warning is logged, since that includes every other
+    extension installed in the browser.
+    """
+    origins = parse_csv(value, [])
+    if origins:
+        return origins, None
+    logger.warning(
+        "%s is not set; accepting requests from any browser extension. "
+        "Set it to this extension's origin (moz-extension://<uuid> from about:debugging).",
+        setting,
+    )
+    return [], EXTENSION_ORIGIN_REGEX
+
+
+def require_loopback_bind(host: str, setting: str) -> None:
+    """Fail fast on a non-loopback bind address; these servers have no auth.
+
+    This only covers the ``__main__`` entry points. ``LoopbackGuardMiddleware``
+    also rejects non-loopback clients however the server was started.
+    """
+    if not is_loopback(host):
+        raise SystemExit(
+            f"{setting}={host!r} is not a loopback address. "
+            "Bind to 127.0.0.1, ::1 or localhost."
+        )
+
+
+def split_host_header(value: str) -> Tuple[Optional[str], Optional[int]]:
+    try:
+        parts = urlsplit(f"//{value}")
+        return parts.hostname, parts.port
+    except ValueError:
+        return None, None
+
+
+class LoopbackGuardMiddleware:
+    """ASGI middleware rejecting requests that did not come from a trusted local caller.
+
+    A DNS-rebound page reaches the server with its own hostname in ``Host``, and
+    CORS alone does not stop a request from running, so the request is checked
+    before the app sees it:
+
+    - the client address must be loopback, so a server started on ``0.0.0.0``
+      (e.g. ``uvicorn --host``) still cannot be driven from the LAN;
+    - ``Host`` must name a loopback address; if it carries a port, that port
+      must be the one the server is listening on;
+    - ``Origin``, when present, must be in ``allowed_origins`` or match
+      ``origin_regex``.
+
+    Tests using Starlette's ``TestClient`` must pass a loopback ``base_url``
+    (e.g. ``http://127.0.0.1``) and ``client=("127.0.0.1", 50000)``; the
+    defaults ``testserver`` and ``testclient`` are rejected.
+    """
+
+    def __init__(
+        self,
+        app: Callable[..., Any],
+        allowed_origins: Iterable[str],
+        origin_regex: Optional[str] = None,
+        port: Optional[int] = None,
+    ) -> None:
+        self.app = app
+        self.allowed_origins = set(allowed_origins)
+        self.origin_regex = re.compile(origin_regex) if origin_regex else None
+        self.port = port
+
+    async def __call__(self, scope: dict, receive: Callable, send: Callable) -> None:
+        if scope["type"] == "http":
+            reason = self._rejection(scope)
+            if reason:
+                await self._forbidden(send, reason)
+                return
+        await self.app(scope, receive, send)
+
+    def _rejection(self, scope: dict) -> Optional[str]:
+        client = scope.get("client")
+        if client and not is_loopback(client[0]):
+            return "Client not allowed."
+
+        headers = {k.decode("latin-1").lower(): v.decode("latin-1") for k, v in scope["headers"]}
+
+        host, port = split_host_header(headers.get("host", ""))
+        if host is None or not is_loopback(host):
+            return "Host not allowed."
+        server = scope.get("server")
+        expected_port = server[1] if server else self.port
+        if port is not None and expected_port is not None and port != expected_port:
+            return "Host port not allowed."
+
+        origin = headers.get("origin")
+        if origin is not None and origin not in self.allowed_origins:
+            if not (self.origin_regex and self.origin_regex.match(origin)):
+                return "Origin not allowed."
+        return None
+
+    @staticmethod
+    async def _forbidden(send: Callable, reason: str) -> None:
+        body = json.dumps({"detail": reason}).encode()
+        await send(
+            {
+                "type": "http.response.start",
+                "status": 403,
+                "headers": [
+                    (b"content-type", b"application/json"),
+                    (b"content-length", str(len(body)).encode()),
+                ],
+            }
+        )
+        await send({"type": "http.response.body", "body": body})
@@ -9,6 +9,13 @@ from fastmcp import FastMCP
 from starlette.middleware import Middleware
 from starlette.middleware.cors import CORSMiddleware
 
+from src.loopback_guard import (
+    LOCAL_ORIGINS,
+    LoopbackGuardMiddleware,
+    extension_origin_policy,
+    parse_csv,
+    require_loopback_bind,
+)
 from src.search import (
     SearchHit,
     rank_hits,
@@ -17,6 +24,13 @@ from src.search import (
     search_text_cached,
 )
 
+MCP_HOST = os.getenv("MCP_HOST", "127.0.0.1")
+MCP_PORT = int(os.getenv("MCP_PORT", "8080"))
+MCP_ORIGINS = parse_csv(os.getenv("MCP_CORS_ORIGINS"), LOCAL_ORIGINS)
+MCP_EXTENSION_ORIGINS, EXTENSION_ORIGIN_PATTERN = extension_origin_policy(
+    os.getenv("MCP_EXTENSION_ORIGINS"), "MCP_EXTENSION_ORIGINS"
+)
+
 mcp = FastMCP("ShoppingSearch")
 
 
@@ -135,14 +149,23 @@ async def shopping_search_image(
 
 # --- ASGI app with CORS for browser clients ---
 
+allowed_origins = MCP_ORIGINS + MCP_EXTENSION_ORIGINS
+
 middleware = [
+    Middleware(
+        LoopbackGuardMiddleware,
+        allowed_origins=allowed_origins,
+        origin_regex=EXTENSION_ORIGIN_PATTERN,
+        port=MCP_PORT,
+    ),
     Middleware(
         CORSMiddleware,
-        allow_origins=["*"],
+        allow_origins=allowed_origins,
+        allow_origin_regex=EXTENSION_ORIGIN_PATTERN,
         allow_methods=["GET", "POST", "DELETE", "OPTIONS"],
         allow_headers=["mcp-protocol-version", "mcp-session-id", "Authorization", "Content-Type"],
         expose_headers=["mcp-session-id"],
-    )
+    ),
 ]
 
 app = mcp.http_app(path="/mcp", middleware=middleware)
@@ -151,7 +174,6 @@ app = mcp.http_app(path="/mcp", middleware=middleware)
 if __name__ == "__main__":
     import uvicorn
 
-    host = os.getenv("MCP_HOST", "127.0.0.1")
-    port = int(os.getenv("MCP_PORT", "8080"))
-    print(f"MCP server starting at http://{host}:{port}/mcp")
-    uvicorn.run(app, host=host, port=port)
+    require_loopback_bind(MCP_HOST, "MCP_HOST")
+    print(f"MCP server starting at http://{MCP_HOST}:{MCP_PORT}/mcp")
+    uvicorn.run(app, host=MCP_HOST, port=MCP_PORT)